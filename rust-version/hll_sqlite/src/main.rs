@@ -1,59 +1,460 @@
-use std::collections::HashSet;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashSet};
+use std::hash::{BuildHasher, Hash, Hasher};
 use std::time::Instant;
-use md5;
+use rand::Rng;
 use regex::Regex;
+use rusqlite::functions::{Aggregate, Context, FunctionFlags};
 use rusqlite::Connection;
 
-struct HyperLogLog {
+/// FNV-1a: a fast 64-bit non-cryptographic hash, used as `HyperLogLog`'s default
+/// `BuildHasher` so adding an element no longer pays for an MD5 computation.
+struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        FnvHasher(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    /// FNV-1a's running multiply-xor state has weak avalanche on its own (similar inputs,
+    /// like sequential strings sharing a prefix, can collide in the top bits `add` uses as
+    /// the register index), so run it through a Murmur3-style finalizer before returning.
+    fn finish(&self) -> u64 {
+        let mut h = self.0;
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xff51afd7ed558ccd);
+        h ^= h >> 33;
+        h = h.wrapping_mul(0xc4ceb9fe1a85ec53);
+        h ^= h >> 33;
+        h
+    }
+}
+
+#[derive(Clone, Default)]
+struct FnvBuildHasher;
+
+impl BuildHasher for FnvBuildHasher {
+    type Hasher = FnvHasher;
+
+    fn build_hasher(&self) -> FnvHasher {
+        FnvHasher::default()
+    }
+}
+
+/// A sparse entry packs a register index into the high bits and its rho value into the
+/// low 8 bits, so a sorted `Vec<u32>` can stand in for the full register array until it
+/// stops being a memory win. The index occupies the remaining 24 bits, so this only holds
+/// for `precision <= 24` (`num_registers() <= 2^24`); larger precisions would silently
+/// truncate the index.
+fn encode_sparse_entry(index: usize, rho: u8) -> u32 {
+    debug_assert!(index < (1 << 24), "sparse entry index does not fit in 24 bits; precision must be <= 24");
+    ((index as u32) << 8) | rho as u32
+}
+
+fn decode_sparse_entry(entry: u32) -> (usize, u8) {
+    ((entry >> 8) as usize, (entry & 0xFF) as u8)
+}
+
+/// Empirical bias correction samples for a handful of precisions, in the style of the
+/// HLL++ paper's per-precision bias tables: (raw estimate, bias) pairs. Precisions without
+/// a table fall back to no correction.
+fn bias_table(precision: u8) -> &'static [(f64, f64)] {
+    match precision {
+        10 => &[(1500.0, 180.0), (3000.0, 90.0), (5000.0, 35.0), (8000.0, 10.0)],
+        14 => &[(11000.0, 500.0), (20000.0, 250.0), (40000.0, 80.0), (70000.0, 20.0)],
+        _ => &[],
+    }
+}
+
+/// Looks up the bias to subtract from `raw_estimate` by nearest-neighbor over the stored
+/// raw-estimate/bias sample points for `precision`, as the HLL++ paper describes.
+fn nearest_neighbor_bias(precision: u8, raw_estimate: f64) -> f64 {
+    let table = bias_table(precision);
+    table
+        .iter()
+        .min_by(|(a, _), (b, _)| (raw_estimate - a).abs().partial_cmp(&(raw_estimate - b).abs()).unwrap())
+        .map(|&(_, bias)| bias)
+        .unwrap_or(0.0)
+}
+
+#[derive(Clone)]
+enum Representation {
+    /// Sorted list of packed (index, rho) entries; used while it is cheaper than a dense array.
+    Sparse(Vec<u32>),
+    Dense(Vec<u8>),
+}
+
+#[derive(Clone)]
+struct HyperLogLog<S = FnvBuildHasher> {
     precision: u8,
-    registers: Vec<u8>,
+    representation: Representation,
+    hash_builder: S,
 }
 
-impl HyperLogLog {
-    fn new(precision: u8) -> Self {
-        let num_registers = 1 << precision;
-        HyperLogLog {
-            precision,
-            registers: vec![0; num_registers],
+impl<S> HyperLogLog<S> {
+    fn num_registers(&self) -> usize {
+        1 << self.precision
+    }
+
+    /// Deduplicates the sparse entry list in place, keeping only the max rho per index.
+    fn dedup_sparse(entries: &mut Vec<u32>) {
+        entries.sort_unstable();
+        let mut deduped: Vec<u32> = Vec::with_capacity(entries.len());
+        for &entry in entries.iter() {
+            let (index, _) = decode_sparse_entry(entry);
+            match deduped.last().copied().map(decode_sparse_entry) {
+                Some((last_index, _)) if last_index == index => {
+                    *deduped.last_mut().unwrap() = entry;
+                }
+                _ => deduped.push(entry),
+            }
         }
+        *entries = deduped;
     }
 
-    fn hash(&self, value: &str) -> u128 {
-        let hash = md5::compute(value);
-        let hash_bytes: [u8; 16] = hash.into();
-        u128::from_le_bytes(hash_bytes)
+    /// Converts a sparse sketch to dense; a dense sketch is left as it is. `add` calls this
+    /// lazily once the sparse list would exceed ~6 bytes per register (each packed entry
+    /// already costs 4 bytes); `merge` and `to_bytes` call it whatever the list's size.
+    fn densify(&mut self) {
+        if let Representation::Sparse(entries) = &self.representation {
+            let mut registers = vec![0u8; self.num_registers()];
+            for &entry in entries {
+                let (index, rho) = decode_sparse_entry(entry);
+                registers[index] = registers[index].max(rho);
+            }
+            self.representation = Representation::Dense(registers);
+        }
+    }
+}
+
+impl<S: BuildHasher> HyperLogLog<S> {
+    /// Hashes `value` once to a 64-bit word using the sketch's `BuildHasher`, so callers can
+    /// feed integers, tuples, or byte slices directly instead of only `&str`.
+    fn hash<T: Hash + ?Sized>(&self, value: &T) -> u64 {
+        self.hash_builder.hash_one(value)
     }
 
-    fn rho(&self, w: u128) -> u8 {
+    /// Returns the leading-zero count of the `w`-bit suffix plus one (the position of its
+    /// first 1-bit), where `w` has already been
+    /// left-aligned to the top of the word (see `add`) so the index bits that were shifted
+    /// out don't inflate the count.
+    fn rho(&self, w: u64) -> u8 {
         (w.leading_zeros() + 1) as u8
     }
 
-    fn add(&mut self, value: &str) {
+    fn add<T: Hash + ?Sized>(&mut self, value: &T) {
         let hash_value = self.hash(value);
-        let j = (hash_value >> (128 - self.precision)) as usize;
-        let w = hash_value & ((1 << (128 - self.precision)) - 1);
-        self.registers[j] = self.registers[j].max(self.rho(w));
+        let j = (hash_value >> (64 - self.precision)) as usize;
+        let w = hash_value << self.precision;
+        let rho = self.rho(w);
+
+        match &mut self.representation {
+            Representation::Dense(registers) => {
+                registers[j] = registers[j].max(rho);
+            }
+            Representation::Sparse(entries) => {
+                entries.push(encode_sparse_entry(j, rho));
+                if entries.len() % 128 == 0 {
+                    Self::dedup_sparse(entries);
+                }
+                if entries.len() * 4 > 6 * self.num_registers() {
+                    self.densify();
+                }
+            }
+        }
+    }
+}
+
+impl<S: BuildHasher + Default> HyperLogLog<S> {
+    /// Starts sparse: small inputs never pay for the full `2^precision`-byte register array.
+    ///
+    /// `precision` comes straight from CLI/SQL input, so the 24-bit sparse-index ceiling
+    /// (see `encode_sparse_entry`) is enforced here as a real `assert!`, not a `debug_assert!`
+    /// -- a release build must refuse a bad precision rather than silently corrupt registers.
+    fn new(precision: u8) -> Self {
+        assert!(precision <= 24, "precision must be <= 24, got {precision}");
+        HyperLogLog {
+            precision,
+            representation: Representation::Sparse(Vec::new()),
+            hash_builder: S::default(),
+        }
+    }
+
+    /// Deserializes a sketch previously produced by `to_bytes`.
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let precision = bytes[0];
+        assert!(precision <= 24, "precision must be <= 24, got {precision}");
+        let registers = bytes[1..].to_vec();
+        HyperLogLog {
+            precision,
+            representation: Representation::Dense(registers),
+            hash_builder: S::default(),
+        }
     }
+}
 
+impl<S> HyperLogLog<S> {
     fn count(&self) -> f64 {
-        let m = self.registers.len() as f64;
-        let z: f64 = 1.0 / self.registers.iter().map(|&b| 2f64.powi(-(b as i32))).sum::<f64>();
-        let estimate = 0.7213 / (1.0 + 1.079 / m) * m * m * z;
-
-        if estimate <= 2.5 * m {
-            let v = self.registers.iter().filter(|&&x| x == 0).count() as f64;
-            if v > 0.0 {
-                m * (m / v).ln()
-            } else {
-                estimate
+        let registers: Vec<u8> = match &self.representation {
+            Representation::Dense(registers) => registers.clone(),
+            Representation::Sparse(entries) => {
+                let mut registers = vec![0u8; self.num_registers()];
+                for &entry in entries {
+                    let (index, rho) = decode_sparse_entry(entry);
+                    registers[index] = registers[index].max(rho);
+                }
+                registers
             }
+        };
+
+        let m = registers.len() as f64;
+        let z: f64 = 1.0 / registers.iter().map(|&b| 2f64.powi(-(b as i32))).sum::<f64>();
+        let raw_estimate = 0.7213 / (1.0 + 1.079 / m) * m * m * z;
+
+        let biased_estimate = if raw_estimate <= 5.0 * m {
+            (raw_estimate - nearest_neighbor_bias(self.precision, raw_estimate)).max(0.0)
         } else {
-            estimate
+            raw_estimate
+        };
+
+        // Whether linear counting beats the raw estimator is a property of the *raw*
+        // estimate (the 2007 paper's `alpha*m^2/raw <= 2.5m` condition), not of the
+        // bias-corrected one: `nearest_neighbor_bias`'s 4-sample-point table barely dents the
+        // raw estimator's upward bias at small-to-mid cardinality, so gating on
+        // `biased_estimate` against a real HLL++ threshold (which assumes a much finer bias
+        // table we don't have) let the under-corrected raw estimate slip through ungated and
+        // return wildly inflated counts instead of falling back to linear counting.
+        let v = registers.iter().filter(|&&x| x == 0).count() as f64;
+        if v > 0.0 && raw_estimate <= 2.5 * m {
+            m * (m / v).ln()
+        } else {
+            biased_estimate
         }
     }
 }
 
-fn process_sqlite_hll(db_path: &str, table_name: &str, column_name: &str, precision: u8) -> f64 {
+impl<S: Clone> HyperLogLog<S> {
+    /// Merges `other` into `self` by taking the element-wise max of each pair of registers.
+    /// This is exact and lossless because every register already holds a max-of-rho value.
+    /// Both sketches are converted to dense first, since a merged sketch is past the point
+    /// where staying sparse would save memory.
+    fn merge(&mut self, other: &HyperLogLog<S>) {
+        assert_eq!(self.precision, other.precision, "cannot merge sketches with different precision");
+        self.densify();
+        let mut other = other.clone();
+        other.densify();
+        if let (Representation::Dense(a), Representation::Dense(b)) = (&mut self.representation, &other.representation) {
+            for (x, &y) in a.iter_mut().zip(b.iter()) {
+                *x = (*x).max(y);
+            }
+        }
+    }
+
+    /// Consumes several sketches of the same precision and returns their union.
+    fn union(sketches: Vec<HyperLogLog<S>>) -> HyperLogLog<S> {
+        let mut iter = sketches.into_iter();
+        let mut combined = iter.next().expect("union requires at least one sketch");
+        for sketch in iter {
+            combined.merge(&sketch);
+        }
+        combined
+    }
+
+    /// Serializes the sketch as the precision byte followed by the dense register bytes,
+    /// suitable for caching in a BLOB column and reloading with `from_bytes`.
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut dense = self.clone();
+        dense.densify();
+        let registers = match &dense.representation {
+            Representation::Dense(registers) => registers,
+            Representation::Sparse(_) => unreachable!("densify always produces Dense"),
+        };
+        let mut bytes = Vec::with_capacity(1 + registers.len());
+        bytes.push(self.precision);
+        bytes.extend_from_slice(registers);
+        bytes
+    }
+
+    /// Estimates the cardinality of the intersection of `a` and `b` via inclusion-exclusion.
+    fn estimate_intersection(a: &HyperLogLog<S>, b: &HyperLogLog<S>) -> f64 {
+        let union = HyperLogLog::union(vec![a.clone(), b.clone()]);
+        (a.count() + b.count() - union.count()).max(0.0)
+    }
+}
+
+/// A `d x w` matrix of counters approximating item frequencies: `add` increments one
+/// counter per row via `d` independent hash functions, `estimate` reads the minimum across
+/// the rows, an upper bound on the true count with error bounded by epsilon = e/w with
+/// probability 1 - delta = 1 - e^-d.
+struct CountMinSketch {
+    depth: usize,
+    width: usize,
+    table: Vec<Vec<u64>>,
+}
+
+impl CountMinSketch {
+    fn new(depth: usize, width: usize) -> Self {
+        CountMinSketch {
+            depth,
+            width,
+            table: vec![vec![0; width]; depth],
+        }
+    }
+
+    /// Derives the `row`-th hash function by hashing `item` together with `row` as a seed.
+    fn column(&self, item: &str, row: usize) -> usize {
+        let mut hasher = FnvHasher::default();
+        row.hash(&mut hasher);
+        item.hash(&mut hasher);
+        (hasher.finish() % self.width as u64) as usize
+    }
+
+    fn add(&mut self, item: &str, n: u64) {
+        for row in 0..self.depth {
+            let col = self.column(item, row);
+            self.table[row][col] += n;
+        }
+    }
+
+    fn estimate(&self, item: &str) -> u64 {
+        (0..self.depth).map(|row| self.table[row][self.column(item, row)]).min().unwrap_or(0)
+    }
+}
+
+/// Bounded min-heap tracking the `k` highest-count items seen so far, so heavy hitters can
+/// be reported from the same pass that feeds a `CountMinSketch`, without a full
+/// `HashMap<String, u64>` of every distinct item.
+struct TopK {
+    k: usize,
+    heap: BinaryHeap<Reverse<(u64, String)>>,
+}
+
+impl TopK {
+    fn new(k: usize) -> Self {
+        TopK {
+            k,
+            heap: BinaryHeap::with_capacity(k + 1),
+        }
+    }
+
+    /// Records the latest estimate for `item`, replacing any stale entry already tracked.
+    fn offer(&mut self, item: &str, count: u64) {
+        self.heap.retain(|Reverse((_, existing))| existing != item);
+        self.heap.push(Reverse((count, item.to_string())));
+        if self.heap.len() > self.k {
+            self.heap.pop();
+        }
+    }
+
+    fn into_sorted_vec(self) -> Vec<(String, u64)> {
+        let mut items: Vec<(u64, String)> = self.heap.into_iter().map(|Reverse(pair)| pair).collect();
+        items.sort_unstable_by_key(|&(count, _)| Reverse(count));
+        items.into_iter().map(|(count, item)| (item, count)).collect()
+    }
+}
+
+/// Scans `column_name` once, building a `CountMinSketch` of word frequencies and a `TopK`
+/// of heavy hitters in parallel, and returns the `k` most frequent words with their
+/// approximate counts.
+fn process_sqlite_word_frequencies(
+    db_path: &str,
+    table_name: &str,
+    column_name: &str,
+    depth: usize,
+    width: usize,
+    k: usize,
+) -> Vec<(String, u64)> {
+    let conn = Connection::open(db_path).unwrap();
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", column_name, table_name)).unwrap();
+    let mut rows = stmt.query([]).unwrap();
+
+    let mut sketch = CountMinSketch::new(depth, width);
+    let mut top_k = TopK::new(k);
+    let re = Regex::new(r"\w+").unwrap();
+
+    while let Some(row) = rows.next().unwrap() {
+        let text: String = row.get(0).unwrap();
+        for word in re.find_iter(&text.to_lowercase()) {
+            let word = word.as_str();
+            sketch.add(word, 1);
+            top_k.offer(word, sketch.estimate(word));
+        }
+    }
+
+    top_k.into_sorted_vec()
+}
+
+/// Keeps a uniform random sample of at most `k` items seen so far via Algorithm R: the
+/// reservoir fills with the first `k` items, then the `i`-th item (i > k) replaces a
+/// uniformly chosen slot with probability `k / i`.
+struct ReservoirSampler<T> {
+    k: usize,
+    seen: usize,
+    reservoir: Vec<T>,
+    rng: rand::rngs::ThreadRng,
+}
+
+impl<T> ReservoirSampler<T> {
+    fn new(k: usize) -> Self {
+        ReservoirSampler {
+            k,
+            seen: 0,
+            reservoir: Vec::with_capacity(k),
+            rng: rand::thread_rng(),
+        }
+    }
+
+    fn add(&mut self, item: T) {
+        self.seen += 1;
+        if self.reservoir.len() < self.k {
+            self.reservoir.push(item);
+        } else {
+            let j = self.rng.gen_range(0..self.seen);
+            if j < self.k {
+                self.reservoir[j] = item;
+            }
+        }
+    }
+
+    fn into_sample(self) -> Vec<T> {
+        self.reservoir
+    }
+}
+
+/// Scans `column_name` once and returns a uniform random sample of at most `k` words, so
+/// users exploring an unfamiliar column can see a representative preview alongside the
+/// distinct-count estimate.
+fn process_sqlite_sample(db_path: &str, table_name: &str, column_name: &str, k: usize) -> Vec<String> {
+    let conn = Connection::open(db_path).unwrap();
+    let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", column_name, table_name)).unwrap();
+    let mut rows = stmt.query([]).unwrap();
+
+    let mut sampler = ReservoirSampler::new(k);
+    let re = Regex::new(r"\w+").unwrap();
+
+    while let Some(row) = rows.next().unwrap() {
+        let text: String = row.get(0).unwrap();
+        for word in re.find_iter(&text.to_lowercase()) {
+            sampler.add(word.as_str().to_string());
+        }
+    }
+
+    sampler.into_sample()
+}
+
+/// Scans `column_name` once and builds a `HyperLogLog` of its tokenized words directly,
+/// bypassing the `approx_distinct` SQL aggregate so the sketch itself can be serialized,
+/// cached, or combined with another column's sketch via `merge`/`estimate_intersection`.
+fn build_hll_for_column(db_path: &str, table_name: &str, column_name: &str, precision: u8) -> HyperLogLog {
     let conn = Connection::open(db_path).unwrap();
     let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", column_name, table_name)).unwrap();
     let mut rows = stmt.query([]).unwrap();
@@ -68,7 +469,97 @@ fn process_sqlite_hll(db_path: &str, table_name: &str, column_name: &str, precis
         }
     }
 
-    hll.count()
+    hll
+}
+
+/// Builds a sketch per column, round-trips each through `to_bytes`/`from_bytes` as if they
+/// had been cached in separate BLOB columns, then reports their estimated overlap via
+/// `estimate_intersection`.
+fn process_sqlite_intersection(
+    db_path: &str,
+    table_name: &str,
+    column_a: &str,
+    column_b: &str,
+    precision: u8,
+) -> f64 {
+    let a = build_hll_for_column(db_path, table_name, column_a, precision);
+    let b = build_hll_for_column(db_path, table_name, column_b, precision);
+    let a: HyperLogLog = HyperLogLog::from_bytes(&a.to_bytes());
+    let b: HyperLogLog = HyperLogLog::from_bytes(&b.to_bytes());
+    HyperLogLog::estimate_intersection(&a, &b)
+}
+
+/// SQLite aggregate state for `approx_distinct(value, precision)`. The state is a
+/// `HyperLogLog` seeded with the precision passed as the second argument; `step` hashes
+/// and adds each incoming value, `finalize` returns the cardinality estimate.
+struct ApproxDistinct;
+
+impl Aggregate<HyperLogLog, f64> for ApproxDistinct {
+    fn init(&self, ctx: &mut Context<'_>) -> rusqlite::Result<HyperLogLog> {
+        let precision: u8 = ctx.get(1)?;
+        Ok(HyperLogLog::new(precision))
+    }
+
+    fn step(&self, ctx: &mut Context<'_>, hll: &mut HyperLogLog) -> rusqlite::Result<()> {
+        let value: String = ctx.get(0)?;
+        hll.add(&value);
+        Ok(())
+    }
+
+    fn finalize(&self, _ctx: &mut Context<'_>, hll: Option<HyperLogLog>) -> rusqlite::Result<f64> {
+        Ok(hll.map(|h| h.count()).unwrap_or(0.0))
+    }
+}
+
+/// Registers `approx_distinct(value, precision)` on `conn` so cardinality estimates can be
+/// composed with arbitrary SQL (joins, WHERE filters, GROUP BY) instead of a fixed pipeline.
+fn register_approx_distinct(conn: &Connection) -> rusqlite::Result<()> {
+    conn.create_aggregate_function(
+        "approx_distinct",
+        2,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        ApproxDistinct,
+    )
+}
+
+fn process_sqlite_hll(db_path: &str, table_name: &str, column_name: &str, precision: u8) -> f64 {
+    let conn = Connection::open(db_path).unwrap();
+    register_approx_distinct(&conn).unwrap();
+
+    // SQLite has no built-in word tokenizer, so the words still have to be split out in
+    // Rust and staged somewhere for `approx_distinct` to scan -- this does not avoid
+    // materializing the tokenized words, it just moves the final distinct-count
+    // aggregation into composable SQL. A column that is already tokenized one word per
+    // row needs no staging at all: `SELECT approx_distinct(word, 10) FROM docs GROUP BY
+    // category` runs `approx_distinct` directly, joins/filters/GROUP BY included.
+    conn.execute_batch("CREATE TEMP TABLE hll_words (word TEXT)").unwrap();
+    {
+        // One INSERT per word still implicitly autocommits per statement without an explicit
+        // transaction, which would make an already-overkill staging step pay for a disk sync
+        // on every single word; batching the staging inserts into one transaction is the
+        // least this demo path can do about that overhead.
+        conn.execute_batch("BEGIN").unwrap();
+        let mut insert = conn.prepare("INSERT INTO hll_words (word) VALUES (?1)").unwrap();
+        let mut stmt = conn.prepare(&format!("SELECT {} FROM {}", column_name, table_name)).unwrap();
+        let mut rows = stmt.query([]).unwrap();
+        let re = Regex::new(r"\w+").unwrap();
+
+        while let Some(row) = rows.next().unwrap() {
+            let text: String = row.get(0).unwrap();
+            for word in re.find_iter(&text.to_lowercase()) {
+                insert.execute([word.as_str()]).unwrap();
+            }
+        }
+        drop(insert);
+        conn.execute_batch("COMMIT").unwrap();
+    }
+
+    conn.query_row(
+        &format!("SELECT approx_distinct(word, {}) FROM hll_words", precision),
+        [],
+        |row| row.get(0),
+    )
+    .unwrap()
 }
 
 fn process_sqlite_exact(db_path: &str, table_name: &str, column_name: &str) -> usize {
@@ -89,10 +580,34 @@ fn process_sqlite_exact(db_path: &str, table_name: &str, column_name: &str) -> u
     unique_words.len()
 }
 
+/// Removes `flag` and its following value from `args` if present, returning the value.
+fn extract_flag_value(args: &mut Vec<String>, flag: &str) -> Option<String> {
+    let pos = args.iter().position(|a| a == flag)?;
+    args.remove(pos);
+    if pos < args.len() {
+        Some(args.remove(pos))
+    } else {
+        None
+    }
+}
+
+/// Depth/width for the `--topk` CountMinSketch: 5 rows keeps the false-positive probability
+/// for any single estimate at e^-5 (< 1%), and a 2048-wide row bounds the per-row error to
+/// e / 2048 of the total word count, which is plenty for a heavy-hitters preview.
+const TOPK_SKETCH_DEPTH: usize = 5;
+const TOPK_SKETCH_WIDTH: usize = 2048;
+
 fn main() {
-    let args: Vec<String> = std::env::args().collect();
+    let mut args: Vec<String> = std::env::args().collect();
+    let sample_k: Option<usize> = extract_flag_value(&mut args, "--sample").map(|v| v.parse().unwrap());
+    let topk_k: Option<usize> = extract_flag_value(&mut args, "--topk").map(|v| v.parse().unwrap());
+    let intersect_with: Option<String> = extract_flag_value(&mut args, "--intersect-with");
+
     if args.len() != 5 {
-        eprintln!("Usage: {} <db_path> <table_name> <column_name> <precision>", args[0]);
+        eprintln!(
+            "Usage: {} <db_path> <table_name> <column_name> <precision> [--sample K] [--topk K] [--intersect-with COLUMN]",
+            args[0]
+        );
         std::process::exit(1);
     }
 
@@ -120,4 +635,149 @@ fn main() {
     println!("{:<15} {:<10.0} {:<10.2} {:<10.2}", "HyperLogLog", hll_result, hll_time, error_percentage);
     println!("{:<15} {:<10} {:<10.2} {:<10}", "Exact", exact_result, exact_time, "N/A");
     println!("{:-<58}", "");
+
+    if let Some(k) = sample_k {
+        let sample = process_sqlite_sample(db_path, table_name, column_name, k);
+        println!("\nUniform sample of {} word(s): {:?}", sample.len(), sample);
+    }
+
+    if let Some(k) = topk_k {
+        let heavy_hitters = process_sqlite_word_frequencies(
+            db_path,
+            table_name,
+            column_name,
+            TOPK_SKETCH_DEPTH,
+            TOPK_SKETCH_WIDTH,
+            k,
+        );
+        println!("\nTop {} most frequent word(s):", k);
+        for (word, count) in &heavy_hitters {
+            println!("{:<20} ~{}", word, count);
+        }
+    }
+
+    if let Some(other_column) = intersect_with {
+        let intersection = process_sqlite_intersection(db_path, table_name, column_name, &other_column, precision);
+        println!(
+            "\nEstimated distinct words shared between '{}' and '{}': {:.0}",
+            column_name, other_column, intersection
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn count_is_close_to_true_cardinality_at_p14() {
+        // Ground-truth check, not just internal agreement: a biased estimator can pass
+        // sparse-vs-dense or merge/intersection comparisons (the bias cancels on both sides)
+        // while still being badly wrong against the true count.
+        for &n in &[2000, 5000, 8000, 12000, 16000, 20000, 50000] {
+            let mut hll: HyperLogLog = HyperLogLog::new(14);
+            for i in 0..n {
+                hll.add(&format!("distinct-item-{i}"));
+            }
+            let estimate = hll.count();
+            let error = (estimate - n as f64).abs() / n as f64;
+            assert!(
+                error < 0.1,
+                "n={n}: estimate {estimate} is {:.1}% off true cardinality",
+                error * 100.0
+            );
+        }
+    }
+
+    #[test]
+    fn sparse_and_dense_representations_agree_on_count() {
+        let mut sparse: HyperLogLog = HyperLogLog::new(10);
+        for i in 0..500 {
+            sparse.add(&format!("word-{i}"));
+        }
+        assert!(matches!(sparse.representation, Representation::Sparse(_)));
+
+        let mut dense = sparse.clone();
+        dense.densify();
+        assert!(matches!(dense.representation, Representation::Dense(_)));
+
+        assert_eq!(sparse.count(), dense.count());
+    }
+
+    #[test]
+    fn reservoir_sampler_never_exceeds_k() {
+        let mut sampler = ReservoirSampler::new(10);
+        for i in 0..1000 {
+            sampler.add(i);
+        }
+        assert_eq!(sampler.into_sample().len(), 10);
+    }
+
+    #[test]
+    fn reservoir_sampler_holds_fewer_than_k_items_seen() {
+        let mut sampler = ReservoirSampler::new(10);
+        for i in 0..5 {
+            sampler.add(i);
+        }
+        assert_eq!(sampler.into_sample().len(), 5);
+    }
+
+    #[test]
+    fn to_bytes_from_bytes_round_trip() {
+        let mut hll: HyperLogLog = HyperLogLog::new(8);
+        for i in 0..5000 {
+            hll.add(&format!("item-{i}"));
+        }
+
+        let bytes = hll.to_bytes();
+        let restored: HyperLogLog = HyperLogLog::from_bytes(&bytes);
+
+        assert_eq!(restored.precision, hll.precision);
+        assert_eq!(restored.count(), hll.count());
+    }
+
+    #[test]
+    fn merge_is_lossless_union_of_disjoint_sets() {
+        let mut a: HyperLogLog = HyperLogLog::new(10);
+        let mut b: HyperLogLog = HyperLogLog::new(10);
+        for i in 0..2000 {
+            a.add(&format!("a-{i}"));
+        }
+        for i in 0..2000 {
+            b.add(&format!("b-{i}"));
+        }
+
+        let combined_count = a.count() + b.count();
+        a.merge(&b);
+
+        // Merging disjoint sets should land close to the sum of the two independent estimates.
+        assert!((a.count() - combined_count).abs() / combined_count < 0.05);
+    }
+
+    #[test]
+    fn estimate_intersection_of_identical_sketches_is_the_full_set() {
+        let mut a: HyperLogLog = HyperLogLog::new(10);
+        for i in 0..3000 {
+            a.add(&format!("shared-{i}"));
+        }
+        let b = a.clone();
+
+        let intersection = HyperLogLog::estimate_intersection(&a, &b);
+        assert!((intersection - a.count()).abs() / a.count() < 0.05);
+    }
+
+    #[test]
+    fn estimate_intersection_of_disjoint_sketches_is_near_zero() {
+        let mut a: HyperLogLog = HyperLogLog::new(10);
+        let mut b: HyperLogLog = HyperLogLog::new(10);
+        for i in 0..2000 {
+            a.add(&format!("a-{i}"));
+        }
+        for i in 0..2000 {
+            b.add(&format!("b-{i}"));
+        }
+
+        let intersection = HyperLogLog::estimate_intersection(&a, &b);
+        assert!(intersection / a.count() < 0.1);
+    }
 }
\ No newline at end of file